@@ -7,13 +7,25 @@ use std::ffi::OsStr;
 use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
 
 use integrators::{Error as IntegratorError, Integrator, IntegratorType, Stats, System};
 use sci_file::{
-    OutputFile, collect_files_from_dir_path, create_directory, create_incremented_directory,
+    collect_files_from_dir_path, create_directory, create_incremented_directory,
     deserialize_json_from_path, serialize_json_to_path,
 };
 
+mod config_override;
+mod output;
+mod verify;
+mod ward;
+
+use config_override::apply_overrides;
+use output::{OutputKind, OutputProcessor, build_outputs, default_outputs};
+use verify::verify_simulation;
+use ward::{WardCheckingOutput, WardConfig, WardHalt};
+
 //#[derive(Parser, Debug)]
 //#[command(version, about, long_about = "Simulation controller.")]
 #[derive(FromArgs, Debug)]
@@ -28,6 +40,18 @@ struct Cli {
     /// path to a directory to write output files. Will be created if it doesn't exist.
     #[argh(positional)]
     output_path: PathBuf,
+    /// override a config field via a dotted path, e.g. `--set universe.diffusivity=0.4`.
+    /// May be repeated. Applied after the `.conf` file is loaded and before it is
+    /// deserialized, so every field is overridable without editing config files.
+    #[argh(option)]
+    set: Vec<String>,
+    /// number of worker threads for batch mode. Defaults to available parallelism.
+    #[argh(option, short = 'j', default = "0")]
+    jobs: usize,
+    /// after each run, read back its jsonl output and check it against a companion
+    /// `.expect` file next to the input config, reporting pass/fail per simulation.
+    #[argh(switch)]
+    verify: bool,
 }
 
 /// This defines the structure of the input config file to be deserialized.
@@ -41,6 +65,84 @@ pub struct InputConfig<U> {
     pub integrator: IntegratorType,
     /// Contains all data available to the derivation function of the integrator.
     pub universe: U,
+    /// Stopping predicates evaluated after every accepted integration step; see [`Ward`].
+    #[serde(default)]
+    pub wards: Vec<WardConfig>,
+    /// Output sinks to compose for this simulation's steps; see [`OutputKind`].
+    /// Defaults to plain JSONL, matching the previous hardwired behaviour.
+    #[serde(default = "default_outputs")]
+    pub outputs: Vec<OutputKind>,
+}
+
+/// Machine-readable outcome of a single [`Simulation::launch`], written as
+/// `outcome.json` next to the simulation's output so batch post-processing can
+/// discover failures without scraping the logfile. Mirrors the distinctions
+/// made in [`log_failure`]; `anyhow::Error` isn't serializable, so `Aborted`
+/// keeps an owned `error_chain` instead of the original error.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "outcome")]
+pub enum RunOutcome {
+    Completed { stats: String },
+    StepLimited { time: f64, n_step: u64 },
+    Halted { reason: String, time: f64, n_step: u64 },
+    Aborted { error_chain: Vec<String> },
+    /// The integration itself ended in `inner`, but `--verify` found the
+    /// recorded output didn't match the `.expect` spec. Counted as a failure
+    /// by [`BatchSummary::exit_code`] regardless of what `inner` was.
+    VerificationFailed {
+        inner: Box<RunOutcome>,
+        failures: Vec<String>,
+    },
+}
+
+/// Paths consulted by `--verify`: the companion `.expect` spec next to the
+/// input config, and the jsonl output to check it against.
+#[derive(Debug, Clone)]
+struct VerifyPaths {
+    expect_path: PathBuf,
+    jsonl_path: PathBuf,
+}
+
+/// One unit of work for [`Simulation::launch_batch`]: a simulation paired with
+/// the initial conditions it should be launched with.
+pub struct LaunchJob<U: Debug, S: System> {
+    pub simulation: Simulation<U, S>,
+    pub x: f64,
+    pub x_final: f64,
+    pub y: Vec<f64>,
+}
+
+/// Aggregate result of a [`Simulation::launch_batch`] run.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BatchSummary {
+    pub succeeded: usize,
+    pub step_limited: usize,
+    pub halted: usize,
+    pub aborted: usize,
+    pub verify_failed: usize,
+}
+
+impl BatchSummary {
+    /// Process exit code for this aggregate: `0` once every simulation completed,
+    /// `1` if any simulation was halted, aborted, or failed `--verify`. A
+    /// simulation reaching its step limit isn't counted as a failure on its
+    /// own; engine/config errors (a missing config directory, an unparseable
+    /// file) surface earlier as an `Err` from
+    /// [`Simulation::new`]/[`Simulation::launch_batch`]'s caller and should
+    /// map to exit code `2` there.
+    ///
+    /// `Simulation<U, S>` is generic over the caller's own universe/system
+    /// types, so this crate has no concrete `main` to call this from — that
+    /// `main`, and the mapping of this code to `std::process::exit`, lives in
+    /// whatever downstream binary picks concrete `U`/`S` and is out of scope
+    /// for this change.
+    pub fn exit_code(&self) -> i32 {
+        if self.halted > 0 || self.aborted > 0 || self.verify_failed > 0 {
+            1
+        } else {
+            0
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -50,16 +152,26 @@ pub struct Simulation<U: Debug, S: System> {
     pub initial_time: f64,
     pub final_time: f64,
     pub integrator: IntegratorType,
-    /// Contains all data passed to the derivation function of the integrator and the `OutputFile` for solout output.
+    /// Contains all data passed to the derivation function of the integrator and the composed output sinks for solout output.
     pub system: S,
+    /// Set by the `WardCheckingOutput` wrapping `system`'s output once a
+    /// configured ward fires; checked by `run` once `integrate` returns.
+    ward_halt: Arc<Mutex<Option<WardHalt>>>,
+    /// Where this simulation's `outcome.json` is written once it ends.
+    outcome_path: PathBuf,
+    /// Set when `--verify` was passed; checked once the run ends.
+    verify: Option<VerifyPaths>,
     /// Placeholder for the generic type `U` that is packaged into the output of `S`. i.e `S::new(T, U)`
     _phantom: PhantomData<U>,
 }
 
-impl<U: Serialize + for<'a> Deserialize<'a> + Debug, S: System<Output = OutputFile, Data = U>>
+impl<U: Serialize + for<'a> Deserialize<'a> + Debug, S: System<Output = Box<dyn OutputProcessor>, Data = U>>
     Simulation<U, S>
 {
-    pub fn new() -> Result<Vec<Simulation<U, S>>> {
+    /// Parses the CLI, builds one `Simulation` per config file, and returns them
+    /// alongside the requested `--jobs` worker count for [`Self::launch_batch`]
+    /// (`0` meaning "use available parallelism").
+    pub fn new() -> Result<(Vec<Simulation<U, S>>, usize)> {
         // Parse arguments.
         let cli: Cli = argh::from_env();
 
@@ -77,24 +189,36 @@ impl<U: Serialize + for<'a> Deserialize<'a> + Debug, S: System<Output = OutputFi
             ))?;
 
         // Parse all the input configs into simulations.
-        if cli.batch_mode {
+        let sims = if cli.batch_mode {
             collect_files_from_dir_path(cli.input_path)?
                 .iter()
                 .filter(|config| config.extension() == Some(OsStr::new("conf")))
-                .map(|config| Self::setup(config, &cli.output_path))
+                .map(|config| Self::setup(config, &cli.output_path, &cli.set, cli.verify))
                 .collect::<Result<Vec<Simulation<U, S>>>>()
         } else {
-            let sim = Self::setup(&cli.input_path, &cli.output_path)?;
+            let sim = Self::setup(&cli.input_path, &cli.output_path, &cli.set, cli.verify)?;
             Ok(vec![sim])
-        }
+        }?;
+
+        Ok((sims, cli.jobs))
     }
 
-    fn setup(input_path: &Path, output_path: &Path) -> Result<Simulation<U, S>> {
-        // Read the values specified in the input file.
-        let config: InputConfig<U> = deserialize_json_from_path(input_path).context(format!(
-            "unable to load config from file: {}",
-            input_path.display()
-        ))?;
+    fn setup(
+        input_path: &Path,
+        output_path: &Path,
+        overrides: &[String],
+        verify: bool,
+    ) -> Result<Simulation<U, S>> {
+        // Read the input file as raw JSON first so `--set` overrides can be merged
+        // in before committing to the strongly typed `InputConfig`.
+        let mut value: serde_json::Value =
+            deserialize_json_from_path(input_path).context(format!(
+                "unable to load config from file: {}",
+                input_path.display()
+            ))?;
+        apply_overrides(&mut value, overrides).context("unable to apply --set override")?;
+        let config: InputConfig<U> = serde_json::from_value(value)
+            .context("unable to parse config after applying --set overrides")?;
 
         ensure!(
             config.initial_time < config.final_time,
@@ -114,13 +238,35 @@ impl<U: Serialize + for<'a> Deserialize<'a> + Debug, S: System<Output = OutputFi
         _ = outpath.set_extension("conf");
         serialize_json_to_path(&config, &outpath)?;
 
-        // Create file to save the output of the simulation.
-        _ = outpath.set_extension("jsonl");
-        let outfile = OutputFile::new(&outpath)?;
+        // Build the composed output sinks for this simulation, writing alongside
+        // the copied config (extension-less base path; each sink sets its own).
+        _ = outpath.set_extension("");
+        let outputs = build_outputs(&config.outputs, &outpath)?;
+        let outcome_path = outpath.with_file_name("outcome.json");
+        let verify = verify.then(|| VerifyPaths {
+            expect_path: input_path.with_extension("expect"),
+            jsonl_path: outpath.with_extension("jsonl"),
+        });
+        ensure!(
+            verify.is_none() || config.outputs.iter().any(|kind| matches!(kind, OutputKind::Jsonl)),
+            "--verify checks \"{}\", but \"{name}\"'s outputs don't include a Jsonl sink",
+            outpath.with_extension("jsonl").display()
+        );
+
+        // Wards can't interrupt `Integrator::integrate` (see `ward.rs`), so
+        // they're evaluated via a `WardCheckingOutput` wrapping the real
+        // outputs; `ward_halt` is how `run` reads back whether one fired.
+        let ward_halt = Arc::new(Mutex::new(None));
+        let wards: Vec<_> = config.wards.iter().map(WardConfig::build).collect();
+        let outputs: Box<dyn OutputProcessor> = if wards.is_empty() {
+            outputs
+        } else {
+            Box::new(WardCheckingOutput::new(outputs, wards, Arc::clone(&ward_halt)))
+        };
 
-        // Create a new `System` with user specified `Universe` structure and `OutputFile`
-        // to pass into the `Integrator`.
-        let system = System::new(outfile, config.universe);
+        // Create a new `System` with user specified `Universe` structure and output
+        // sinks to pass into the `Integrator`.
+        let system = System::new(outputs, config.universe);
 
         Ok(Self {
             name,
@@ -129,12 +275,22 @@ impl<U: Serialize + for<'a> Deserialize<'a> + Debug, S: System<Output = OutputFi
             final_time: config.final_time,
             integrator: config.integrator,
             system,
+            ward_halt,
+            outcome_path,
+            verify,
             _phantom: PhantomData,
         })
     }
 
     // Launch a single simulation, logging results to the logfile.
-    pub fn launch(mut self, x: f64, x_final: f64, y: &[f64]) -> Result<()> {
+    pub fn launch(self, x: f64, x_final: f64, y: &[f64]) -> Result<()> {
+        self.run(x, x_final, y)?;
+        Ok(())
+    }
+
+    // Shared by `launch` and `launch_batch`: runs the integration, logs the
+    // result, and reports which of the terminal states it ended in.
+    fn run(mut self, x: f64, x_final: f64, y: &[f64]) -> Result<RunOutcome> {
         // Apply the initial values for a new simulation.
         // For a resume simulation the values will already be in the integrator snapshot.
         if !self.resume {
@@ -143,12 +299,148 @@ impl<U: Serialize + for<'a> Deserialize<'a> + Debug, S: System<Output = OutputFi
 
         // Run the integration and check the result.
         log_start(&self.name, self.initial_time, self.final_time);
-        match self.integrator.integrate(&mut self.system) {
-            Ok(stats) => log_success(&self.name, self.final_time, &stats),
+        let outcome = match self.integrator.integrate(&mut self.system) {
+            Ok(stats) => {
+                log_success(&self.name, self.final_time, &stats);
+                RunOutcome::Completed {
+                    stats: stats.to_string(),
+                }
+            }
             Err(why) => log_failure(&self.name, &anyhow!(why)),
+        };
+
+        // A ward may have fired partway through the integration above: wards
+        // are evaluated by the `WardCheckingOutput` wrapping this system's
+        // output (see `ward.rs`), since `integrate` has no way to be told to
+        // stop early. Whatever it recorded takes priority over the plain
+        // integration result.
+        let outcome = match self.ward_halt.lock().expect("ward halt mutex poisoned").take() {
+            Some(halt) => {
+                warn!(
+                    "Halting simulation {} at time {} (step {}): {}",
+                    self.name, halt.x, halt.n_step, halt.reason
+                );
+                RunOutcome::Halted {
+                    reason: halt.reason,
+                    time: halt.x,
+                    n_step: halt.n_step,
+                }
+            }
+            None => outcome,
+        };
+
+        // Checked after the outcome is determined but before it's written, so a
+        // failed `--verify` check is reflected in `outcome.json` and trips a
+        // non-zero batch exit code instead of only showing up in the logfile.
+        let outcome = match &self.verify {
+            Some(paths) => match verify_simulation(&paths.expect_path, &paths.jsonl_path) {
+                Ok(None) => outcome,
+                Ok(Some(failures)) => {
+                    for failure in &failures {
+                        error!("simulation {}: verification FAILED: {failure}", self.name);
+                    }
+                    RunOutcome::VerificationFailed {
+                        inner: Box::new(outcome),
+                        failures,
+                    }
+                }
+                Err(why) => {
+                    log_error_chain(
+                        &why,
+                        format!("unable to verify simulation {}", self.name),
+                    );
+                    outcome
+                }
+            },
+            None => outcome,
+        };
+
+        // Written regardless of outcome so batch post-processing can discover
+        // failures without scraping the logfile.
+        // A failure to write the side-channel outcome file (disk full, permissions)
+        // is logged, not propagated: it must not override an already-computed
+        // integration outcome with a spurious `Err`/`Aborted`.
+        if let Err(why) = serialize_json_to_path(&outcome, &self.outcome_path) {
+            log_error_chain(
+                &anyhow!(why),
+                format!(
+                    "unable to write outcome for simulation {} to \"{}\"",
+                    self.name,
+                    self.outcome_path.display()
+                ),
+            );
         }
 
-        Ok(())
+        Ok(outcome)
+    }
+}
+
+impl<U, S> Simulation<U, S>
+where
+    U: Serialize + for<'a> Deserialize<'a> + Debug + Send + 'static,
+    S: System<Output = Box<dyn OutputProcessor>, Data = U> + Send + 'static,
+{
+    /// Runs `jobs` across a bounded pool of `num_jobs` worker threads, falling
+    /// back to [`std::thread::available_parallelism`] when `num_jobs` is `0`.
+    /// Jobs are fed to the pool through a channel and each worker owns its
+    /// simulation's `System`/output sinks outright, so the `jsonl` writers never
+    /// contend with one another.
+    pub fn launch_batch(jobs: Vec<LaunchJob<U, S>>, num_jobs: usize) -> BatchSummary {
+        let num_jobs = if num_jobs == 0 {
+            thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+        } else {
+            num_jobs
+        }
+        .max(1)
+        .min(jobs.len().max(1));
+
+        let (job_tx, job_rx) = mpsc::channel::<LaunchJob<U, S>>();
+        for job in jobs {
+            // Never fails: the receiver side is dropped only after this loop.
+            _ = job_tx.send(job);
+        }
+        drop(job_tx);
+        let job_rx = Arc::new(Mutex::new(job_rx));
+
+        let (result_tx, result_rx) = mpsc::channel::<Result<RunOutcome>>();
+        thread::scope(|scope| {
+            for _ in 0..num_jobs {
+                let job_rx = Arc::clone(&job_rx);
+                let result_tx = result_tx.clone();
+                scope.spawn(move || {
+                    // `recv` is called through a short-lived lock and the guard
+                    // dropped immediately after: holding it for the loop body
+                    // (as `while let Ok(job) = job_rx.lock()....recv() { ... }`
+                    // would) serializes every worker on whichever one is
+                    // currently running a job, defeating the pool entirely.
+                    loop {
+                        let next = job_rx.lock().expect("job queue poisoned").recv();
+                        let Ok(job) = next else { break };
+                        let name = job.simulation.name.clone();
+                        let outcome = job.simulation.run(job.x, job.x_final, &job.y);
+                        if let Err(why) = &outcome {
+                            warn!("simulation {name}: unable to start: {why}");
+                        }
+                        // Ignore send errors: the receiver only disconnects once every
+                        // worker has finished and the final summary has been tallied.
+                        _ = result_tx.send(outcome);
+                    }
+                });
+            }
+            drop(result_tx);
+        });
+
+        let mut summary = BatchSummary::default();
+        for outcome in result_rx {
+            match outcome {
+                Ok(RunOutcome::Completed { .. }) => summary.succeeded += 1,
+                Ok(RunOutcome::StepLimited { .. }) => summary.step_limited += 1,
+                Ok(RunOutcome::Halted { .. }) => summary.halted += 1,
+                Ok(RunOutcome::Aborted { .. }) | Err(_) => summary.aborted += 1,
+                Ok(RunOutcome::VerificationFailed { .. }) => summary.verify_failed += 1,
+            }
+        }
+        summary
     }
 }
 
@@ -169,16 +461,27 @@ fn log_success(name: &str, final_time: f64, stats: &Stats) {
     );
 }
 
-fn log_failure(name: &str, why: &AnyError) {
+fn log_failure(name: &str, why: &AnyError) -> RunOutcome {
     match why.downcast_ref() {
         // Integration terminated early due to maximum number of steps reached.
-        Some(IntegratorError::StepLimitReached { x: time, n_step }) => warn!(
-            "Terminating simulation {} after {} years as maximum {n_step} steps reached.",
-            name, time
-        ),
+        Some(IntegratorError::StepLimitReached { x: time, n_step }) => {
+            warn!(
+                "Terminating simulation {} after {} years as maximum {n_step} steps reached.",
+                name, time
+            );
+            RunOutcome::StepLimited {
+                time: *time,
+                n_step: *n_step,
+            }
+        }
         // Other integration error, check the log for specifics.
-        _ => log_error_chain(why, format!("Aborting simulation {name} due to failure.")),
-    };
+        _ => {
+            log_error_chain(why, format!("Aborting simulation {name} due to failure."));
+            RunOutcome::Aborted {
+                error_chain: why.chain().map(|cause| cause.to_string()).collect(),
+            }
+        }
+    }
 }
 
 // Unwinds chains of errors, flattening them into a single log entry.
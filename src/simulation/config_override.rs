@@ -0,0 +1,126 @@
+use anyhow::{Context, Result, anyhow};
+use serde_json::Value;
+
+/// Parses and applies every `--set key.path=value` flag onto `root`, a config
+/// loaded as raw JSON.
+///
+/// `path` is a dotted key (`universe.diffusivity`); intermediate objects are
+/// created as needed. The right-hand side is parsed as a `bool`, then an
+/// `i64`, then an `f64`, falling back to a JSON string, so both numbers and
+/// flags coerce to the type the target field expects.
+pub fn apply_overrides(root: &mut Value, overrides: &[String]) -> Result<()> {
+    for entry in overrides {
+        let (path, raw) = entry
+            .split_once('=')
+            .with_context(|| format!("--set \"{entry}\" is missing a \"=value\" part"))?;
+        set_path(root, path, parse_scalar(raw))?;
+    }
+    Ok(())
+}
+
+fn parse_scalar(raw: &str) -> Value {
+    if let Ok(b) = raw.parse::<bool>() {
+        return Value::Bool(b);
+    }
+    if let Ok(i) = raw.parse::<i64>() {
+        return Value::Number(i.into());
+    }
+    if let Ok(f) = raw.parse::<f64>() {
+        if let Some(n) = serde_json::Number::from_f64(f) {
+            return Value::Number(n);
+        }
+    }
+    Value::String(raw.to_string())
+}
+
+/// Walks `path` into `root`, creating nested objects as needed, and sets the
+/// final key to `value`.
+fn set_path(root: &mut Value, path: &str, value: Value) -> Result<()> {
+    let mut node = root;
+    let mut parts = path.split('.').peekable();
+    while let Some(key) = parts.next() {
+        if !node.is_object() && !node.is_null() {
+            return Err(anyhow!(
+                "--set path \"{path}\" tries to set \"{key}\" inside a non-object field"
+            ));
+        }
+        if node.is_null() {
+            *node = Value::Object(Default::default());
+        }
+        let object = node.as_object_mut().expect("checked above");
+        if parts.peek().is_none() {
+            object.insert(key.to_string(), value);
+            return Ok(());
+        }
+        node = object
+            .entry(key.to_string())
+            .or_insert_with(|| Value::Object(Default::default()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn set_path_creates_intermediate_objects() {
+        let mut root = json!({});
+
+        set_path(&mut root, "universe.diffusivity", json!(0.5)).unwrap();
+
+        assert_eq!(root, json!({"universe": {"diffusivity": 0.5}}));
+    }
+
+    #[test]
+    fn set_path_overwrites_an_existing_leaf() {
+        let mut root = json!({"universe": {"diffusivity": 0.1, "other": true}});
+
+        set_path(&mut root, "universe.diffusivity", json!(0.9)).unwrap();
+
+        assert_eq!(root, json!({"universe": {"diffusivity": 0.9, "other": true}}));
+    }
+
+    #[test]
+    fn set_path_rejects_descending_into_a_scalar_field() {
+        let mut root = json!({"universe": 1.0});
+
+        let err = set_path(&mut root, "universe.diffusivity", json!(0.9)).unwrap_err();
+
+        assert!(err.to_string().contains("non-object field"));
+    }
+
+    #[test]
+    fn parse_scalar_prefers_bool_then_int_then_float_then_string() {
+        assert_eq!(parse_scalar("true"), Value::Bool(true));
+        assert_eq!(parse_scalar("42"), json!(42));
+        assert_eq!(parse_scalar("1.5"), json!(1.5));
+        assert_eq!(parse_scalar("oak"), Value::String("oak".to_string()));
+    }
+
+    #[test]
+    fn apply_overrides_merges_several_dotted_paths() {
+        let mut root = json!({"universe": {"diffusivity": 0.1}});
+
+        apply_overrides(
+            &mut root,
+            &["universe.diffusivity=0.2".to_string(), "resume=true".to_string()],
+        )
+        .unwrap();
+
+        assert_eq!(
+            root,
+            json!({"universe": {"diffusivity": 0.2}, "resume": true})
+        );
+    }
+
+    #[test]
+    fn apply_overrides_rejects_an_entry_without_equals() {
+        let mut root = json!({});
+
+        let err = apply_overrides(&mut root, &["universe.diffusivity".to_string()]).unwrap_err();
+
+        assert!(err.to_string().contains("missing a \"=value\" part"));
+    }
+}
@@ -0,0 +1,341 @@
+use anyhow::{Context, Result};
+use log::warn;
+use sci_file::OutputFile;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// A sink that observes every accepted integration step.
+///
+/// Implementations may buffer, transform, decimate, or drop records entirely.
+/// [`OutputProcessor::finalize`] is called exactly once after integration ends
+/// (successfully or not) so sinks can flush any pending summary.
+pub trait OutputProcessor {
+    fn record(&mut self, x: f64, y: &[f64]);
+    fn finalize(&mut self);
+}
+
+/// Runs every configured processor, in order, for each step.
+pub struct CompositeOutput {
+    processors: Vec<Box<dyn OutputProcessor>>,
+}
+
+impl OutputProcessor for CompositeOutput {
+    fn record(&mut self, x: f64, y: &[f64]) {
+        for processor in &mut self.processors {
+            processor.record(x, y);
+        }
+    }
+
+    fn finalize(&mut self) {
+        for processor in &mut self.processors {
+            processor.finalize();
+        }
+    }
+}
+
+/// Describes one output sink, deserialized from the `outputs` field of
+/// [`crate::simulation::InputConfig`]. Several may be combined for a single
+/// simulation; see [`build_outputs`].
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "kind")]
+pub enum OutputKind {
+    /// Raw JSONL, one record per accepted step.
+    Jsonl,
+    /// A flat CSV with a header row derived from the state vector length.
+    Csv,
+    /// Wraps another sink, forwarding only every `every`th accepted step to it.
+    Decimated { every: usize, inner: Box<OutputKind> },
+    /// Accumulates per-component min/max/mean and writes one summary record
+    /// on completion.
+    Stats,
+}
+
+impl OutputKind {
+    fn build(&self, base_path: &Path) -> Result<Box<dyn OutputProcessor>> {
+        match self {
+            OutputKind::Jsonl => {
+                let mut path = base_path.to_path_buf();
+                path.set_extension("jsonl");
+                Ok(Box::new(JsonlOutput::new(OutputFile::new(&path)?)))
+            }
+            OutputKind::Csv => {
+                let mut path = base_path.to_path_buf();
+                path.set_extension("csv");
+                Ok(Box::new(CsvOutput::new(path)?))
+            }
+            OutputKind::Decimated { every, inner } => Ok(Box::new(DecimatedOutput::new(
+                *every,
+                inner.build(base_path)?,
+            ))),
+            OutputKind::Stats => {
+                let mut path = base_path.to_path_buf();
+                path.set_extension("stats.json");
+                Ok(Box::new(StatsOutput::new(path)))
+            }
+        }
+    }
+}
+
+/// Builds the composed [`OutputProcessor`] for a simulation from its configured
+/// `outputs`, all writing alongside `base_path` (extension-less).
+pub fn build_outputs(kinds: &[OutputKind], base_path: &Path) -> Result<Box<dyn OutputProcessor>> {
+    let processors = kinds
+        .iter()
+        .map(|kind| kind.build(base_path))
+        .collect::<Result<Vec<_>>>()
+        .context("unable to set up an output processor")?;
+    Ok(Box::new(CompositeOutput { processors }))
+}
+
+/// Default `outputs` for configs that don't specify one, preserving the
+/// previous always-JSONL behaviour.
+pub fn default_outputs() -> Vec<OutputKind> {
+    vec![OutputKind::Jsonl]
+}
+
+/// Writes one JSONL record per accepted step via the existing `sci_file::OutputFile`.
+struct JsonlOutput {
+    file: OutputFile,
+}
+
+impl JsonlOutput {
+    fn new(file: OutputFile) -> Self {
+        Self { file }
+    }
+}
+
+#[derive(Serialize)]
+struct Record<'a> {
+    x: f64,
+    y: &'a [f64],
+}
+
+impl OutputProcessor for JsonlOutput {
+    fn record(&mut self, x: f64, y: &[f64]) {
+        _ = self.file.write(&Record { x, y });
+    }
+
+    fn finalize(&mut self) {}
+}
+
+/// Writes a flat CSV, with the header (`x,y0,y1,...`) emitted before the first record.
+struct CsvOutput {
+    writer: BufWriter<File>,
+    header_written: bool,
+}
+
+impl CsvOutput {
+    fn new(path: PathBuf) -> Result<Self> {
+        let file = File::create(&path)
+            .with_context(|| format!("unable to create csv output \"{}\"", path.display()))?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            header_written: false,
+        })
+    }
+
+    fn write_header(&mut self, n: usize) {
+        let mut header = String::from("x");
+        for i in 0..n {
+            header.push_str(&format!(",y{i}"));
+        }
+        _ = writeln!(self.writer, "{header}");
+        self.header_written = true;
+    }
+}
+
+impl OutputProcessor for CsvOutput {
+    fn record(&mut self, x: f64, y: &[f64]) {
+        if !self.header_written {
+            self.write_header(y.len());
+        }
+        let mut row = x.to_string();
+        for v in y {
+            row.push(',');
+            row.push_str(&v.to_string());
+        }
+        _ = writeln!(self.writer, "{row}");
+    }
+
+    fn finalize(&mut self) {
+        _ = self.writer.flush();
+    }
+}
+
+/// Forwards only every `every`th accepted step to an inner processor.
+struct DecimatedOutput {
+    every: usize,
+    count: usize,
+    inner: Box<dyn OutputProcessor>,
+}
+
+impl DecimatedOutput {
+    fn new(every: usize, inner: Box<dyn OutputProcessor>) -> Self {
+        Self {
+            every: every.max(1),
+            count: 0,
+            inner,
+        }
+    }
+}
+
+impl OutputProcessor for DecimatedOutput {
+    fn record(&mut self, x: f64, y: &[f64]) {
+        if self.count % self.every == 0 {
+            self.inner.record(x, y);
+        }
+        self.count += 1;
+    }
+
+    fn finalize(&mut self) {
+        self.inner.finalize();
+    }
+}
+
+/// Accumulates per-component min/max/mean and writes a single summary record
+/// at [`OutputProcessor::finalize`].
+struct StatsOutput {
+    path: PathBuf,
+    count: u64,
+    min: Vec<f64>,
+    max: Vec<f64>,
+    sum: Vec<f64>,
+}
+
+impl StatsOutput {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            count: 0,
+            min: Vec::new(),
+            max: Vec::new(),
+            sum: Vec::new(),
+        }
+    }
+}
+
+impl OutputProcessor for StatsOutput {
+    fn record(&mut self, _x: f64, y: &[f64]) {
+        if self.count == 0 {
+            self.min = y.to_vec();
+            self.max = y.to_vec();
+            self.sum = y.to_vec();
+        } else if y.len() == self.min.len() {
+            for (i, &v) in y.iter().enumerate() {
+                self.min[i] = self.min[i].min(v);
+                self.max[i] = self.max[i].max(v);
+                self.sum[i] += v;
+            }
+        } else {
+            // The state vector's length is expected to be fixed for a given
+            // `System`; a record that doesn't match the first one seen can't
+            // be folded into per-component min/max/sum without panicking, so
+            // it's dropped rather than accepted as a partial update.
+            warn!(
+                "StatsOutput: dropping record with {} component(s), expected {}",
+                y.len(),
+                self.min.len()
+            );
+            return;
+        }
+        self.count += 1;
+    }
+
+    fn finalize(&mut self) {
+        if self.count == 0 {
+            return;
+        }
+        let mean: Vec<f64> = self.sum.iter().map(|s| s / self.count as f64).collect();
+        let summary = json!({
+            "count": self.count,
+            "min": self.min,
+            "max": self.max,
+            "mean": mean,
+        });
+        if let Ok(file) = File::create(&self.path) {
+            _ = serde_json::to_writer_pretty(file, &summary);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Forwards every `(x, y)` it's given into a shared log, for asserting on
+    /// forwarding behaviour from outside the `Box<dyn OutputProcessor>` it's wrapped in.
+    struct SpyOutput {
+        recorded: Rc<RefCell<Vec<f64>>>,
+        finalized: Rc<RefCell<usize>>,
+    }
+
+    impl OutputProcessor for SpyOutput {
+        fn record(&mut self, x: f64, _y: &[f64]) {
+            self.recorded.borrow_mut().push(x);
+        }
+
+        fn finalize(&mut self) {
+            *self.finalized.borrow_mut() += 1;
+        }
+    }
+
+    #[test]
+    fn decimated_output_forwards_every_nth_record_starting_at_the_first() {
+        let recorded = Rc::new(RefCell::new(Vec::new()));
+        let finalized = Rc::new(RefCell::new(0));
+        let spy = SpyOutput {
+            recorded: Rc::clone(&recorded),
+            finalized: Rc::clone(&finalized),
+        };
+        let mut decimated = DecimatedOutput::new(3, Box::new(spy));
+        for i in 0..7 {
+            decimated.record(i as f64, &[i as f64]);
+        }
+        decimated.finalize();
+
+        assert_eq!(*recorded.borrow(), vec![0.0, 3.0, 6.0]);
+        assert_eq!(*finalized.borrow(), 1);
+    }
+
+    #[test]
+    fn decimated_output_treats_every_zero_as_every_one() {
+        let recorded = Rc::new(RefCell::new(Vec::new()));
+        let spy = SpyOutput {
+            recorded: Rc::clone(&recorded),
+            finalized: Rc::new(RefCell::new(0)),
+        };
+        let mut decimated = DecimatedOutput::new(0, Box::new(spy));
+        for i in 0..3 {
+            decimated.record(i as f64, &[i as f64]);
+        }
+
+        assert_eq!(recorded.borrow().len(), 3);
+    }
+
+    #[test]
+    fn stats_output_tracks_min_max_sum_across_records() {
+        let mut stats = StatsOutput::new(PathBuf::from("unused"));
+        stats.record(0.0, &[1.0, -1.0]);
+        stats.record(1.0, &[3.0, -5.0]);
+
+        assert_eq!(stats.count, 2);
+        assert_eq!(stats.min, vec![1.0, -5.0]);
+        assert_eq!(stats.max, vec![3.0, -1.0]);
+        assert_eq!(stats.sum, vec![4.0, -6.0]);
+    }
+
+    #[test]
+    fn stats_output_drops_records_whose_length_differs_from_the_first() {
+        let mut stats = StatsOutput::new(PathBuf::from("unused"));
+        stats.record(0.0, &[1.0, 2.0]);
+        stats.record(1.0, &[3.0]);
+
+        assert_eq!(stats.count, 1);
+        assert_eq!(stats.min, vec![1.0, 2.0]);
+    }
+}
@@ -0,0 +1,273 @@
+use anyhow::{Context, Result};
+use sci_file::deserialize_json_from_path;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// A "final time" expectation: the state at the last recorded step must be
+/// within `abs_tol`/`rel_tol` of `value` at `index`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ExpectedFinal {
+    pub index: usize,
+    pub value: f64,
+    #[serde(default)]
+    pub abs_tol: f64,
+    #[serde(default)]
+    pub rel_tol: f64,
+}
+
+/// A bound that must hold at every recorded step.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ExpectedBound {
+    pub index: usize,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Deserialized form of the `.expect` file next to a `.conf`: the final-state
+/// and in-flight bounds that [`verify_simulation`] checks a completed run's
+/// jsonl output against.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct ExpectSpec {
+    #[serde(default)]
+    pub final_values: Vec<ExpectedFinal>,
+    #[serde(default)]
+    pub bounds: Vec<ExpectedBound>,
+}
+
+/// One mismatch found while checking a simulation's jsonl output against its
+/// `.expect` spec.
+#[derive(Debug, Clone)]
+enum VerifyFailure {
+    FinalValue {
+        index: usize,
+        expected: f64,
+        actual: f64,
+    },
+    /// A `final_values` expectation that couldn't even be evaluated, e.g. the
+    /// run produced no recorded steps or its final state has no component at `index`.
+    FinalValueMissing {
+        index: usize,
+        reason: String,
+    },
+    Bound {
+        index: usize,
+        step: usize,
+        value: f64,
+        min: f64,
+        max: f64,
+    },
+}
+
+impl fmt::Display for VerifyFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyFailure::FinalValue {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "final state[{index}] = {actual} does not match expected {expected}"
+            ),
+            VerifyFailure::FinalValueMissing { index, reason } => {
+                write!(f, "expected final state[{index}] but {reason}")
+            }
+            VerifyFailure::Bound {
+                index,
+                step,
+                value,
+                min,
+                max,
+            } => write!(
+                f,
+                "state[{index}] = {value} left bounds [{min}, {max}] at step {step}"
+            ),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Record {
+    x: f64,
+    y: Vec<f64>,
+}
+
+/// Reads back `jsonl_path` line by line and checks it against `spec`: every
+/// bound is checked at every step (only its first violation is kept) and the
+/// final-time expectations are checked against the last recorded record.
+fn check(jsonl_path: &Path, spec: &ExpectSpec) -> Result<Vec<VerifyFailure>> {
+    let file = File::open(jsonl_path)
+        .with_context(|| format!("unable to open \"{}\" for verification", jsonl_path.display()))?;
+
+    let mut bound_violations: Vec<Option<(usize, f64)>> = vec![None; spec.bounds.len()];
+    let mut last: Option<Record> = None;
+
+    for (step, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.with_context(|| {
+            format!("unable to read \"{}\" at line {step}", jsonl_path.display())
+        })?;
+        let record: Record = serde_json::from_str(&line).with_context(|| {
+            format!("unable to parse \"{}\" at line {step}", jsonl_path.display())
+        })?;
+        for (bound, violation) in spec.bounds.iter().zip(bound_violations.iter_mut()) {
+            if violation.is_some() {
+                continue;
+            }
+            if let Some(&v) = record.y.get(bound.index) {
+                if v < bound.min || v > bound.max {
+                    *violation = Some((step, v));
+                }
+            }
+        }
+        last = Some(record);
+    }
+
+    let mut failures = Vec::new();
+    for (bound, violation) in spec.bounds.iter().zip(bound_violations) {
+        if let Some((step, value)) = violation {
+            failures.push(VerifyFailure::Bound {
+                index: bound.index,
+                step,
+                value,
+                min: bound.min,
+                max: bound.max,
+            });
+        }
+    }
+
+    match &last {
+        // No recorded steps at all: every final-time expectation is unevaluable,
+        // not silently satisfied.
+        None => {
+            for expected in &spec.final_values {
+                failures.push(VerifyFailure::FinalValueMissing {
+                    index: expected.index,
+                    reason: "the run produced no recorded steps".to_string(),
+                });
+            }
+        }
+        Some(last) => {
+            for expected in &spec.final_values {
+                let Some(&actual) = last.y.get(expected.index) else {
+                    failures.push(VerifyFailure::FinalValueMissing {
+                        index: expected.index,
+                        reason: format!(
+                            "the final state only has {} component(s)",
+                            last.y.len()
+                        ),
+                    });
+                    continue;
+                };
+                let tol = expected.abs_tol.max(expected.rel_tol * expected.value.abs());
+                if (actual - expected.value).abs() > tol {
+                    failures.push(VerifyFailure::FinalValue {
+                        index: expected.index,
+                        expected: expected.value,
+                        actual,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(failures)
+}
+
+/// Verifies a completed simulation's `jsonl_path` output against the
+/// companion `expect_path` spec, returning the stringified failures (if any)
+/// for the caller to log and fold into the run's [`crate::simulation::RunOutcome`].
+/// Simulations without a companion `.expect` file are skipped rather than
+/// treated as a failure, so `--verify` can be enabled batch-wide while only
+/// some configs carry specs.
+pub fn verify_simulation(expect_path: &Path, jsonl_path: &Path) -> Result<Option<Vec<String>>> {
+    if !expect_path.exists() {
+        return Ok(None);
+    }
+
+    let spec: ExpectSpec = deserialize_json_from_path(expect_path)
+        .with_context(|| format!("unable to load expect spec \"{}\"", expect_path.display()))?;
+    let failures = check(jsonl_path, &spec)?;
+
+    if failures.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(failures.iter().map(ToString::to_string).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn write_jsonl(unique: &str, lines: &[&str]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("simulation-verify-test-{unique}.jsonl"));
+        let mut file = File::create(&path).unwrap();
+        for line in lines {
+            writeln!(file, "{line}").unwrap();
+        }
+        path
+    }
+
+    #[test]
+    fn no_recorded_steps_fails_final_value_expectations() {
+        let path = write_jsonl("empty", &[]);
+        let spec = ExpectSpec {
+            final_values: vec![ExpectedFinal {
+                index: 0,
+                value: 1.0,
+                abs_tol: 0.0,
+                rel_tol: 0.0,
+            }],
+            bounds: vec![],
+        };
+
+        let failures = check(&path, &spec).unwrap();
+
+        assert_eq!(failures.len(), 1);
+        assert!(matches!(failures[0], VerifyFailure::FinalValueMissing { .. }));
+        _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn final_state_shorter_than_expected_index_fails() {
+        let path = write_jsonl("short-state", &[r#"{"x":0.0,"y":[1.0]}"#]);
+        let spec = ExpectSpec {
+            final_values: vec![ExpectedFinal {
+                index: 5,
+                value: 1.0,
+                abs_tol: 0.0,
+                rel_tol: 0.0,
+            }],
+            bounds: vec![],
+        };
+
+        let failures = check(&path, &spec).unwrap();
+
+        assert_eq!(failures.len(), 1);
+        assert!(matches!(failures[0], VerifyFailure::FinalValueMissing { .. }));
+        _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn matching_final_value_within_tolerance_passes() {
+        let path = write_jsonl("match", &[r#"{"x":0.0,"y":[1.0,2.0]}"#]);
+        let spec = ExpectSpec {
+            final_values: vec![ExpectedFinal {
+                index: 1,
+                value: 2.0,
+                abs_tol: 1e-9,
+                rel_tol: 0.0,
+            }],
+            bounds: vec![],
+        };
+
+        let failures = check(&path, &spec).unwrap();
+
+        assert!(failures.is_empty());
+        _ = std::fs::remove_file(path);
+    }
+}
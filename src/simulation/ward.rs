@@ -0,0 +1,183 @@
+use super::output::OutputProcessor;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+/// Outcome of evaluating a single [`Ward`] at an accepted integration step.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WardResult {
+    /// The simulation may continue.
+    Continue,
+    /// The simulation must stop immediately for the given reason.
+    Halt { reason: String },
+}
+
+/// A user-defined stopping predicate evaluated after every accepted step.
+///
+/// Wards let a run detect broken physical invariants (non-finite state, a
+/// tracked quantity leaving a sane band, ...). [`Integrator::integrate`]
+/// has no hook for stopping a run early, so wards can't interrupt the
+/// integration loop itself; instead [`WardCheckingOutput`] evaluates them at
+/// the same point the run's output is already observed (every accepted
+/// step) and records the first violation for [`crate::simulation::Simulation::run`]
+/// to report as a `Halted` outcome once `integrate` returns.
+pub trait Ward: Send + Sync {
+    fn check(&self, x: f64, y: &[f64]) -> WardResult;
+}
+
+impl std::fmt::Debug for dyn Ward {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("<ward>")
+    }
+}
+
+/// Serializable description of a [`Ward`], deserialized from the `wards` field
+/// of [`crate::simulation::InputConfig`].
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "kind")]
+pub enum WardConfig {
+    /// Halt as soon as `x` or any state component is `NaN` or infinite.
+    NonFinite,
+    /// Halt if the state component at `index` leaves the inclusive `[min, max]` band.
+    Bounds { index: usize, min: f64, max: f64 },
+    /// Halt once the wall-clock budget for the run, counted from its first
+    /// accepted step, has been exceeded.
+    MaxWallClock { secs: u64 },
+}
+
+impl WardConfig {
+    /// Instantiates the runtime [`Ward`] described by this config.
+    pub fn build(&self) -> Box<dyn Ward> {
+        match *self {
+            WardConfig::NonFinite => Box::new(NonFinite),
+            WardConfig::Bounds { index, min, max } => Box::new(Bounds { index, min, max }),
+            WardConfig::MaxWallClock { secs } => Box::new(MaxWallClock::new(secs)),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct NonFinite;
+
+impl Ward for NonFinite {
+    fn check(&self, x: f64, y: &[f64]) -> WardResult {
+        if !x.is_finite() || y.iter().any(|v| !v.is_finite()) {
+            return WardResult::Halt {
+                reason: "non-finite value encountered in simulation state".to_string(),
+            };
+        }
+        WardResult::Continue
+    }
+}
+
+#[derive(Debug)]
+struct Bounds {
+    index: usize,
+    min: f64,
+    max: f64,
+}
+
+impl Ward for Bounds {
+    fn check(&self, _x: f64, y: &[f64]) -> WardResult {
+        match y.get(self.index) {
+            Some(&v) if v < self.min || v > self.max => WardResult::Halt {
+                reason: format!(
+                    "state[{}] = {v} left bounds [{}, {}]",
+                    self.index, self.min, self.max
+                ),
+            },
+            _ => WardResult::Continue,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct MaxWallClock {
+    secs: u64,
+    started: std::sync::OnceLock<std::time::Instant>,
+}
+
+impl MaxWallClock {
+    fn new(secs: u64) -> Self {
+        Self {
+            secs,
+            started: std::sync::OnceLock::new(),
+        }
+    }
+}
+
+impl Ward for MaxWallClock {
+    fn check(&self, _x: f64, _y: &[f64]) -> WardResult {
+        let started = self.started.get_or_init(std::time::Instant::now);
+        if started.elapsed().as_secs() >= self.secs {
+            return WardResult::Halt {
+                reason: format!("wall clock budget of {}s exceeded", self.secs),
+            };
+        }
+        WardResult::Continue
+    }
+}
+
+/// The first ward violation seen by a [`WardCheckingOutput`], recorded so
+/// `Simulation::run` can report it once integration returns.
+#[derive(Debug, Clone)]
+pub struct WardHalt {
+    pub reason: String,
+    pub x: f64,
+    pub n_step: u64,
+}
+
+/// Wraps the real output pipeline, evaluating every configured [`Ward`] at
+/// each accepted step before forwarding the record unchanged.
+///
+/// The first violation is written to `halt` and every step after that is
+/// still forwarded — there's no way to tell the already in-flight
+/// `integrate` call to stop short of it returning on its own — but the wards
+/// are no longer evaluated, so `halt` always reflects where the state first
+/// went bad rather than where it was last observed.
+pub struct WardCheckingOutput {
+    inner: Box<dyn OutputProcessor>,
+    wards: Vec<Box<dyn Ward>>,
+    halt: Arc<Mutex<Option<WardHalt>>>,
+    step: u64,
+}
+
+impl WardCheckingOutput {
+    pub fn new(
+        inner: Box<dyn OutputProcessor>,
+        wards: Vec<Box<dyn Ward>>,
+        halt: Arc<Mutex<Option<WardHalt>>>,
+    ) -> Self {
+        Self {
+            inner,
+            wards,
+            halt,
+            step: 0,
+        }
+    }
+}
+
+impl OutputProcessor for WardCheckingOutput {
+    fn record(&mut self, x: f64, y: &[f64]) {
+        self.inner.record(x, y);
+        self.step += 1;
+
+        let mut halt = self.halt.lock().expect("ward halt mutex poisoned");
+        if halt.is_some() {
+            return;
+        }
+        for ward in &self.wards {
+            if let WardResult::Halt { reason } = ward.check(x, y) {
+                *halt = Some(WardHalt {
+                    reason,
+                    x,
+                    n_step: self.step,
+                });
+                break;
+            }
+        }
+    }
+
+    fn finalize(&mut self) {
+        self.inner.finalize();
+    }
+}